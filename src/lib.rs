@@ -7,9 +7,57 @@ use std::sync::Arc;
 
 struct Dive {
 	params: Arc<WhammyParams>,
-	buffer: Vec<f32>,
+	// One ring buffer per channel so each keeps its own independent delay line -
+	// the dive/depth params are shared, but the audio itself is not.
+	buffers: Vec<Vec<f32>>,
 	write_pos: usize,
-  read_pos: usize,
+  // Fractional position of read tap A, advanced each sample at a rate of `1 - pitch`
+  // rather than locked to 1 sample/tick, which is what actually produces the shift.
+  read_phase: f32,
+  // Triangular crossfade window, looked up by each tap's normalized delay distance
+  // to the write head (the splice point), so that whichever tap is nearing it
+  // fades out instead of clicking.
+  envelope: Vec<f32>,
+  // Needed to drive the dive smoother's target directly from incoming CC events.
+  sample_rate: f32,
+  // Current host tempo (BPM) and transport state, refreshed each `process` call
+  // so the sync'd LFO can read them from inside `write`.
+  tempo: f32,
+  transport_playing: bool,
+  // Running LFO position used to derive the LFO phase. In sync mode it counts
+  // beats, only advances while the transport is playing, and is re-synced from the
+  // host position at the start of every block to avoid drifting out of time; in
+  // free-run mode it counts LFO cycles at `lfo_rate`.
+  lfo_beat_pos: f32,
+}
+
+// Upper bound of the `dive` param's range, duplicated here so the CC handler can
+// map a 0-1 controller value onto it without reaching into `FloatRange`.
+const DIVE_MAX: f32 = 0.5;
+
+/// Note division the tempo-synced dive LFO can be locked to.
+#[derive(Enum, Debug, PartialEq)]
+enum SyncDivision {
+  #[id = "1/4"]
+  Quarter,
+  #[id = "1/8"]
+  Eighth,
+  #[id = "1/8t"]
+  EighthTriplet,
+  #[id = "1/16"]
+  Sixteenth,
+}
+
+impl SyncDivision {
+  // Length of one cycle of this division, in beats.
+  fn beats(&self) -> f32 {
+    match self {
+      SyncDivision::Quarter => 1.,
+      SyncDivision::Eighth => 0.5,
+      SyncDivision::EighthTriplet => 1. / 3.,
+      SyncDivision::Sixteenth => 0.25,
+    }
+  }
 }
 
 #[derive(Params)]
@@ -22,16 +70,46 @@ struct WhammyParams {
 	pub dive: FloatParam,
 
   #[id = "depth"]
-  pub depth: FloatParam
+  pub depth: FloatParam,
+
+  /// CC number the dive amount listens to when `cc_enabled` is on (CC#11
+  /// expression and CC#4 foot pedal are the usual choices).
+  #[id = "cc_number"]
+  pub cc_number: IntParam,
+
+  #[id = "cc_enabled"]
+  pub cc_enabled: BoolParam,
+
+  /// Locks the dive LFO to the host's tempo/transport instead of free-running.
+  #[id = "lfo_sync"]
+  pub lfo_sync: BoolParam,
+
+  #[id = "lfo_division"]
+  pub lfo_division: EnumParam<SyncDivision>,
+
+  /// Only used when `lfo_sync` is off.
+  #[id = "lfo_rate"]
+  pub lfo_rate: FloatParam,
+
+  #[id = "lfo_depth"]
+  pub lfo_depth: FloatParam,
+
+  /// Fraction of the pitch-shifted output fed back into the delay line. Since
+  /// each repeat is itself pitch-shifted, feedback makes the bend cascade
+  /// further with every pass instead of just repeating.
+  #[id = "feedback"]
+  pub feedback: FloatParam,
+
+  #[id = "mix"]
+  pub mix: FloatParam,
 }
 
 impl Default for Dive {
 	fn default() -> Self {
-		let mut buffer: Vec<f32> = Vec::new();
-		// Zero out ring buffer
-			for _ in 0..(44100 * 3) {
-				buffer.push(0.);
-			}
+		// Real buffers are allocated per-channel in `initialize` once we know the
+		// actual channel count and sample rate - until then these are empty
+		// placeholders with no room to read or write.
+		let buffers: Vec<Vec<f32>> = vec![Vec::new(); 2];
 
 		let mut envelope: Vec<f32> = Vec::new();
 		let inc: f32 = 1. / 22050.;
@@ -49,9 +127,14 @@ impl Default for Dive {
 
 		Self {
 			params: Arc::new(WhammyParams::default()),
-			buffer,
+			buffers,
 			write_pos: 0,
-      read_pos: 0
+      read_phase: 0.,
+      envelope,
+      sample_rate: 44100.,
+      tempo: 120.,
+      transport_playing: false,
+      lfo_beat_pos: 0.,
 		}
 	}
 }
@@ -76,7 +159,39 @@ impl Default for WhammyParams {
           min: 0.,
           max: 3.
         }
-      ).with_smoother(SmoothingStyle::Linear(250.))
+      ).with_smoother(SmoothingStyle::Linear(250.)),
+
+      cc_number: IntParam::new("CC Number", 11, IntRange::Linear { min: 0, max: 127 }),
+
+      cc_enabled: BoolParam::new("CC Control", false),
+
+      lfo_sync: BoolParam::new("LFO Host Sync", false),
+
+      lfo_division: EnumParam::new("LFO Division", SyncDivision::Quarter),
+
+      lfo_rate: FloatParam::new(
+        "LFO Rate",
+        2.,
+        FloatRange::Linear { min: 0.05, max: 10. },
+      ),
+
+      lfo_depth: FloatParam::new(
+        "LFO Depth",
+        0.,
+        FloatRange::Linear { min: 0., max: 1. },
+      ).with_smoother(SmoothingStyle::Linear(50.)),
+
+      feedback: FloatParam::new(
+        "Feedback",
+        0.,
+        FloatRange::Linear { min: 0., max: 0.95 },
+      ).with_smoother(SmoothingStyle::Linear(50.)),
+
+      mix: FloatParam::new(
+        "Mix",
+        1.,
+        FloatRange::Linear { min: 0., max: 1. },
+      ).with_smoother(SmoothingStyle::Linear(50.)),
 		}
 	}
 }
@@ -113,11 +228,21 @@ impl Plugin for Dive {
 
 	fn initialize(
 		&mut self,
-		_audio_io_layout: &AudioIOLayout,
-		_buffer_config: &BufferConfig,
-		context: &mut impl InitContext<Self>,
+		audio_io_layout: &AudioIOLayout,
+		buffer_config: &BufferConfig,
+		_context: &mut impl InitContext<Self>,
 	) -> bool {
-    // TODO: allocate here
+    let num_channels = audio_io_layout
+      .main_input_channels
+      .map(|c| c.get() as usize)
+      .unwrap_or(2);
+    let buffer_len = (buffer_config.sample_rate * 3.0) as usize;
+
+    self.buffers = vec![vec![0.; buffer_len]; num_channels];
+    self.write_pos = 0;
+    self.read_phase = 0.;
+    self.sample_rate = buffer_config.sample_rate;
+
 		true
 	}
 
@@ -127,11 +252,30 @@ impl Plugin for Dive {
 		&mut self,
 		buffer: &mut Buffer,
 		_aux: &mut AuxiliaryBuffers,
-		_context: &mut impl ProcessContext<Self>,
+		context: &mut impl ProcessContext<Self>,
 	) -> ProcessStatus {
+    while let Some(event) = context.next_event() {
+      if let NoteEvent::MidiCC { cc, value, .. } = event {
+        if self.params.cc_enabled.value() && cc as i32 == self.params.cc_number.value() {
+          self.params.dive.smoothed.set_target(self.sample_rate, value * DIVE_MAX);
+        }
+      }
+    }
+
+    let transport = context.transport();
+    self.tempo = transport.tempo.unwrap_or(self.tempo as f64) as f32;
+    self.transport_playing = transport.playing;
+    if self.transport_playing && self.params.lfo_sync.value() {
+      if let Some(pos_beats) = transport.pos_beats() {
+        self.lfo_beat_pos = pos_beats as f32;
+      }
+    }
+
+    let block_start_write_pos = self.write_pos;
+
     self.read(buffer);
 
-    self.write(buffer);
+    self.write(buffer, block_start_write_pos);
 
 		ProcessStatus::Normal
 	}
@@ -139,53 +283,139 @@ impl Plugin for Dive {
 
 impl Dive {
     fn read(&mut self, buffer: &mut Buffer) {
+      let buffer_len = self.buffers[0].len();
+
       for channel_samples in buffer.iter_samples() {
-        // TODO: support > 1 channel
-        for sample in channel_samples {
-          self.buffer[self.write_pos] = *sample;
-          break;
+        for (channel, sample) in channel_samples.into_iter().enumerate() {
+          if let Some(channel_buffer) = self.buffers.get_mut(channel) {
+            channel_buffer[self.write_pos] = *sample;
+          }
         }
 
-        self.write_pos = (self.write_pos + 1) % self.buffer.len();
+        self.write_pos = (self.write_pos + 1) % buffer_len;
       }
     }
 
-    fn write(&mut self, buffer: &mut Buffer) {
+    fn write(&mut self, buffer: &mut Buffer, start_pos: usize) {
+      let buffer_len = self.buffers[0].len();
+      let buffer_len_f = buffer_len as f32;
+      // Tracks the same ring buffer position `read` wrote each sample to, so the
+      // feedback path can mix back into the right slot.
+      let mut feedback_pos = start_pos;
+
       for channel_samples in buffer.iter_samples() {
-        let delayed_read = self.read_pos as f32 - 1. -
-          self.params.dive.smoothed.next() * self.params.depth.smoothed.next() * 1000.;
-        let wrapped = if delayed_read < 0. {
-          self.buffer.len() as f32 + delayed_read // TODO: store buffer len as float in struct
-        } else {
-          delayed_read % self.buffer.len() as f32
-        };
-
-        for sample in channel_samples {
-          *sample = interpolate_2(&wrapped, &self.buffer)
+        let lfo = self.next_lfo_sample();
+        let pitch = self.params.dive.smoothed.next() * self.params.depth.smoothed.next() + lfo;
+
+        // Two read taps, half a buffer length apart, crossfaded against each other.
+        // Whichever tap is closest to lapping the write head is the one the envelope
+        // fades down, so the splice never produces an audible discontinuity.
+        let tap_a = self.read_phase;
+        let tap_b = (tap_a + buffer_len_f / 2.) % buffer_len_f;
+
+        // Gain is keyed off each tap's delay distance to the write head (the only
+        // place a real discontinuity exists), not the tap's own absolute position -
+        // those wrap at a different, pitch-dependent rate and so drift out of sync
+        // with the actual splice point.
+        let delay_a = (feedback_pos as f32 - tap_a).rem_euclid(buffer_len_f) / buffer_len_f;
+        let delay_b = (delay_a + 0.5) % 1.;
+        let gain_a = self.envelope_at(delay_a);
+        let gain_b = self.envelope_at(delay_b);
+
+        let feedback = self.params.feedback.smoothed.next();
+        let mix = self.params.mix.smoothed.next();
+
+        for (channel, sample) in channel_samples.into_iter().enumerate() {
+          if let Some(channel_buffer) = self.buffers.get_mut(channel) {
+            let a = interpolate_2(&tap_a, channel_buffer);
+            let b = interpolate_2(&tap_b, channel_buffer);
+            let wet = a * gain_a + b * gain_b;
+
+            // Regenerate a fraction of the pitch-shifted signal back into the delay
+            // line instead of only ever storing the dry input, so each repeat bends
+            // further - soft-clipped to keep runaway feedback from blowing up.
+            let dry = channel_buffer[feedback_pos];
+            channel_buffer[feedback_pos] = soft_clip(dry + wet * feedback);
+
+            *sample = dry * (1. - mix) + wet * mix;
+          }
+        }
+
+        feedback_pos = (feedback_pos + 1) % buffer_len;
+
+        self.read_phase = (self.read_phase + (1. - pitch)) % buffer_len_f;
+        if self.read_phase < 0. {
+          self.read_phase += buffer_len_f;
         }
-        self.read_pos = (self.read_pos + 1) % self.buffer.len();
       }
     }
+
+    // Looks up the crossfade gain for a tap whose normalized 0..1 delay distance
+    // behind the write head is `phase`, using the triangular envelope built in `Default`.
+    fn envelope_at(&self, phase: f32) -> f32 {
+      let idx = (phase * self.envelope.len() as f32) as usize;
+      self.envelope[idx.min(self.envelope.len() - 1)]
+    }
+
+    // Advances the dive LFO by one sample and returns its current value, scaled
+    // by depth. Phase is held steady while the transport is stopped so synced
+    // bends don't jump when playback resumes.
+    fn next_lfo_sample(&mut self) -> f32 {
+      // `lfo_division` is a sync-only concept - it divides the host's beat clock,
+      // not the free-run rate, so it must never rescale the free-run branch below.
+      let phase = if self.params.lfo_sync.value() {
+        if self.transport_playing {
+          let beats_per_sample = (self.tempo / 60.) / self.sample_rate;
+          self.lfo_beat_pos += beats_per_sample;
+        }
+
+        let division_beats = self.params.lfo_division.value().beats();
+        (self.lfo_beat_pos / division_beats).rem_euclid(1.)
+      } else {
+        let hz = self.params.lfo_rate.smoothed.next();
+        self.lfo_beat_pos += hz / self.sample_rate;
+
+        self.lfo_beat_pos.rem_euclid(1.)
+      };
+
+      (phase * std::f32::consts::TAU).sin() * self.params.lfo_depth.smoothed.next()
+    }
 }
 
+// Keeps the feedback path bounded without hard-clipping it.
+fn soft_clip(sample: f32) -> f32 {
+  sample.tanh()
+}
+
+// 4-point cubic Hermite (Catmull-Rom) interpolation. Linear interpolation
+// low-pass-filters and adds aliasing distortion at the large pitch ratios a
+// divebomb produces; this stays smooth across the whole dive/depth range.
 fn interpolate_2(f_idx: &f32, buffer: &Vec<f32>) -> f32 {
-  // y1 + (x - x1) * (y2 - y1) / (x2 - x1)
-  // low_sample + (f_idx - low_idx) * (high_sample - low_sample) / (high_idx - low_idx) = 1\
-  // low_sample + (f_idx - low_idx) * (high_sample - low_sample)
-  let clamped = f_idx.clamp(0., buffer.len() as f32);
-
-
-  let low_idx = clamped as usize;
-  let high_idx = low_idx + 1;
-  let high_idx_wrapped = if high_idx >= buffer.len() {
-    high_idx - buffer.len()
-  } else {
-    high_idx
+  let len = buffer.len();
+  let clamped = f_idx.rem_euclid(len as f32);
+
+  let i = clamped as usize;
+  let t = clamped - i as f32;
+
+  let wrap = |idx: isize| -> usize {
+    (idx.rem_euclid(len as isize)) as usize
   };
-  let low_sample = buffer[low_idx];
-  let high_sample = buffer[high_idx_wrapped];
 
-  low_sample + (f_idx - (low_idx as f32)) * (high_sample - low_sample)
+  let y0 = buffer[wrap(i as isize - 1)];
+  let y1 = buffer[wrap(i as isize)];
+  let y2 = buffer[wrap(i as isize + 1)];
+  let y3 = buffer[wrap(i as isize + 2)];
+
+  let m1 = 0.5 * (y2 - y0);
+  let m2 = 0.5 * (y3 - y1);
+
+  let t2 = t * t;
+  let t3 = t2 * t;
+
+  (2. * t3 - 3. * t2 + 1.) * y1
+    + (t3 - 2. * t2 + t) * m1
+    + (-2. * t3 + 3. * t2) * y2
+    + (t3 - t2) * m2
 }
 
 impl ClapPlugin for Dive {